@@ -0,0 +1,105 @@
+//! Transitive `#include`/import dependency resolution for [`FilePrompt`].
+//!
+//! [`FilePrompt`]: crate::Commands::FilePrompt
+
+use crate::path_pattern::PathPattern;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Returns the language-specific regexes used to find dependency paths in a
+/// file with the given extension. Each regex's first capture group is the
+/// dependency path as written in the source.
+fn include_patterns(ext: &str) -> Vec<Regex> {
+    match ext {
+        "c" | "h" | "cpp" | "cc" | "cxx" | "hpp" | "hh" => {
+            vec![Regex::new(r#"#include\s+"(.*?)""#).unwrap()]
+        }
+        "rs" => vec![Regex::new(r"(?m)^\s*(?:pub\s+)?mod\s+(\w+)\s*;").unwrap()],
+        "py" => vec![Regex::new(r"(?m)^\s*(?:from|import)\s+([\w\.]+)").unwrap()],
+        "js" | "jsx" | "ts" | "tsx" => {
+            vec![Regex::new(r#"(?:from\s+|require\()\s*['"](\.[^'"]*)['"]"#).unwrap()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Resolves a captured dependency path to a file on disk, relative to `dir`
+/// (the including file's directory). Rust `mod`/Python `import` captures are
+/// dotted module paths (`a.b`), so dots are rewritten to path separators
+/// before resolution; C/C++ `#include` paths and JS/TS relative imports are
+/// already filesystem paths and are tried verbatim. Each candidate is then
+/// tried as-is, with the including file's extension appended, and as a
+/// directory containing a conventional entry point (`mod.rs`, `__init__.py`,
+/// `index.<ext>`).
+fn resolve_include(dir: &Path, captured: &str, ext: &str) -> Option<PathBuf> {
+    let relative = if matches!(ext, "rs" | "py") {
+        captured.replace('.', "/")
+    } else {
+        captured.to_string()
+    };
+    let base = dir.join(relative);
+    let mut with_ext = base.clone().into_os_string();
+    with_ext.push(format!(".{}", ext));
+
+    let candidates = [
+        base.clone(),
+        PathBuf::from(with_ext),
+        base.join(format!("mod.{}", ext)),
+        base.join(format!("__init__.{}", ext)),
+        base.join(format!("index.{}", ext)),
+    ];
+
+    candidates.into_iter().find(|c| c.is_file())
+}
+
+/// Scans `path` and its transitive dependencies, returning them in
+/// dependency order (leaves first, `path` itself last). A visited set keyed
+/// by canonicalized path prevents cycles and duplicate emission. Dependencies
+/// matching `ignore_globs` (relative to their including file's directory) are
+/// pruned from the graph.
+pub fn resolve_file_graph(path: &Path, ignore_globs: &[PathPattern]) -> io::Result<Vec<PathBuf>> {
+    let mut visited = HashSet::new();
+    let mut order = Vec::new();
+    visit(path, ignore_globs, &mut visited, &mut order)?;
+    Ok(order)
+}
+
+fn visit(
+    path: &Path,
+    ignore_globs: &[PathPattern],
+    visited: &mut HashSet<PathBuf>,
+    order: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let canonical = fs::canonicalize(path)?;
+    if !visited.insert(canonical) {
+        return Ok(());
+    }
+
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let contents = fs::read_to_string(path).unwrap_or_default();
+
+    for pattern in include_patterns(ext) {
+        for caps in pattern.captures_iter(&contents) {
+            let Some(captured) = caps.get(1) else { continue };
+            let Some(dep_path) = resolve_include(dir, captured.as_str(), ext) else { continue };
+
+            let relative = dep_path
+                .strip_prefix(dir)
+                .unwrap_or(&dep_path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if ignore_globs.iter().any(|p| p.matches(&relative)) {
+                continue;
+            }
+
+            visit(&dep_path, ignore_globs, visited, order)?;
+        }
+    }
+
+    order.push(path.to_path_buf());
+    Ok(())
+}
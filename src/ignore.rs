@@ -0,0 +1,181 @@
+//! Auto-loading of `.gitignore`/`.ignore` files, mirroring the subset of
+//! ripgrep/fd behavior needed by the directory walker: discovery of ignore
+//! files while walking, and gitignore-compatible pattern matching with
+//! last-match-wins precedence.
+
+use crate::path_pattern::PathPattern;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// A single compiled rule from a `.gitignore`/`.ignore` file.
+struct IgnoreRule {
+    /// Glob pattern, relative to `root`.
+    glob: PathPattern,
+    /// `true` for a `!`-prefixed whitelist (negated) pattern.
+    negate: bool,
+    /// `true` if the pattern ended in `/` and only matches directories.
+    dir_only: bool,
+    /// Directory the pattern is resolved relative to (the ignore file's own
+    /// directory).
+    root: PathBuf,
+}
+
+impl IgnoreRule {
+    /// Parses a single gitignore line, returning `None` for blank lines and
+    /// comments.
+    fn parse(line: &str, root: &Path) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut pattern = line;
+
+        let negate = pattern.starts_with('!');
+        if negate {
+            pattern = &pattern[1..];
+        }
+
+        let dir_only = pattern.ends_with('/');
+        if dir_only {
+            pattern = &pattern[..pattern.len() - 1];
+        }
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let anchored = pattern.contains('/') || pattern.starts_with('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+        let glob = PathPattern::with_anchor(pattern, anchored).ok()?;
+
+        Some(IgnoreRule {
+            glob,
+            negate,
+            dir_only,
+            root: root.to_path_buf(),
+        })
+    }
+
+    /// Tests whether this rule matches `relative` (the candidate path,
+    /// already relative to `root`). Anchoring is baked into `glob` itself.
+    fn matches(&self, relative: &str) -> bool {
+        self.glob.matches(relative)
+    }
+}
+
+/// The parsed rules from a single `.gitignore`/`.ignore` file.
+pub struct IgnoreFile {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    /// Loads and parses the ignore file at `path`. Patterns within it are
+    /// resolved relative to `root` (the directory the file lives in).
+    pub fn load(path: &Path, root: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let mut rules = Vec::new();
+        for line in reader.lines() {
+            if let Some(rule) = IgnoreRule::parse(&line?, root) {
+                rules.push(rule);
+            }
+        }
+        Ok(IgnoreFile { rules })
+    }
+}
+
+/// Accumulated ignore rules discovered for a walk, in gitignore precedence
+/// order: rules are applied in discovery order and the *last* matching rule
+/// wins, so a later whitelist (`!pattern`) re-includes a path an earlier
+/// rule excluded.
+pub struct Ignore {
+    rules: Vec<IgnoreRule>,
+    load_gitignore: bool,
+    load_dot_ignore: bool,
+}
+
+impl Ignore {
+    /// Discovers ignore files for a walk rooted at `target`: walks upward
+    /// from `target` collecting `.gitignore`/`.ignore` files, stopping once a
+    /// `.git` directory is encountered (or the filesystem root is reached).
+    /// `target` is not made absolute first, so for a relative `target` the
+    /// walk only visits the ancestors written in it, ending with the current
+    /// directory (the empty path); directories above that are not searched.
+    pub fn discover(target: &Path, load_gitignore: bool, load_dot_ignore: bool) -> Self {
+        let mut rules = Vec::new();
+
+        if load_gitignore || load_dot_ignore {
+            let mut ancestors = Vec::new();
+            let mut current = Some(target.to_path_buf());
+            while let Some(dir) = current {
+                let stop = dir.join(".git").exists();
+                ancestors.push(dir.clone());
+                if stop {
+                    break;
+                }
+                current = dir.parent().map(|p| p.to_path_buf());
+            }
+            // `ancestors` is target-to-root; reverse so the outermost
+            // directory's rules are applied first, matching gitignore's
+            // "closer rules take precedence" behavior under last-wins.
+            ancestors.reverse();
+            for dir in &ancestors {
+                Self::load_dir(dir, load_gitignore, load_dot_ignore, &mut rules);
+            }
+        }
+
+        Ignore {
+            rules,
+            load_gitignore,
+            load_dot_ignore,
+        }
+    }
+
+    fn load_dir(dir: &Path, load_gitignore: bool, load_dot_ignore: bool, rules: &mut Vec<IgnoreRule>) {
+        if load_gitignore {
+            let gitignore = dir.join(".gitignore");
+            if gitignore.is_file() {
+                if let Ok(file) = IgnoreFile::load(&gitignore, dir) {
+                    rules.extend(file.rules);
+                }
+            }
+        }
+        if load_dot_ignore {
+            let dot_ignore = dir.join(".ignore");
+            if dot_ignore.is_file() {
+                if let Ok(file) = IgnoreFile::load(&dot_ignore, dir) {
+                    rules.extend(file.rules);
+                }
+            }
+        }
+    }
+
+    /// Picks up any `.gitignore`/`.ignore` file that lives in `dir`,
+    /// extending the rule set as the walk descends into nested directories.
+    pub fn push_dir(&mut self, dir: &Path) {
+        Self::load_dir(dir, self.load_gitignore, self.load_dot_ignore, &mut self.rules);
+    }
+
+    /// Returns whether `path` should be excluded, applying last-match-wins
+    /// precedence across every rule discovered so far.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let relative = match path.strip_prefix(&rule.root) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if rule.matches(&relative) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
@@ -1,9 +1,14 @@
-use clap::{Parser, Subcommand};
-use glob::Pattern;
+mod deps;
+mod ignore;
+mod path_pattern;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use ignore::Ignore;
+use path_pattern::PathPattern;
 use std::error::Error;
 use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tiktoken_rs::{p50k_base};
 
 /// A command-line utility for processing files in a directory
@@ -15,6 +20,17 @@ struct Cli {
     command: Commands,
 }
 
+/// Priority order in which files are considered for `--max-tokens` packing
+#[derive(Clone, Copy, ValueEnum)]
+enum SortKey {
+    /// Smallest files first (by byte size)
+    Size,
+    /// Smallest files first (by token count)
+    Tokens,
+    /// Alphabetical by relative path
+    Name,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Tokenize contents of files in the specified directory and display token counts
@@ -33,6 +49,18 @@ enum Commands {
         /// Skip lines containing the specified substring. Can be used multiple times
         #[arg(short, long, value_name = "SUBSTRING")]
         skip: Vec<String>,
+
+        /// Limit recursion to the given number of directory levels below the root
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Don't auto-load .gitignore files
+        #[arg(long)]
+        no_vcs_ignore: bool,
+
+        /// Don't auto-load .gitignore or .ignore files
+        #[arg(long)]
+        no_ignore: bool,
     },
     /// Build prompts from file names and their contents
     DirPrompt {
@@ -50,6 +78,47 @@ enum Commands {
         /// Skip lines containing the specified substring. Can be used multiple times
         #[arg(short, long, value_name = "SUBSTRING")]
         skip: Vec<String>,
+
+        /// Limit recursion to the given number of directory levels below the root
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Don't auto-load .gitignore files
+        #[arg(long)]
+        no_vcs_ignore: bool,
+
+        /// Don't auto-load .gitignore or .ignore files
+        #[arg(long)]
+        no_ignore: bool,
+
+        /// Greedily pack files until this many tokens have been used, omitting the rest
+        #[arg(long, value_name = "N")]
+        max_tokens: Option<usize>,
+
+        /// Order in which files are considered for packing
+        #[arg(long, value_enum, default_value_t = SortKey::Name)]
+        sort: SortKey,
+    },
+    /// Build a prompt from a seed file and its transitive #include/import graph
+    FilePrompt {
+        /// Path to the seed source file
+        file: String,
+
+        /// Specify output file path. If not provided, outputs to stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+
+        /// Ignore files matching the given glob pattern. Can be used multiple times
+        #[arg(short, long, value_name = "GLOB")]
+        ignore: Vec<String>,
+
+        /// Skip lines containing the specified substring. Can be used multiple times
+        #[arg(short, long, value_name = "SUBSTRING")]
+        skip: Vec<String>,
+
+        /// Print a tiktoken token total for the assembled prompt
+        #[arg(long)]
+        tokens: bool,
     },
 }
 
@@ -62,8 +131,19 @@ fn main() {
             output,
             ignore,
             skip,
+            max_depth,
+            no_vcs_ignore,
+            no_ignore,
         } => {
-            if let Err(e) = tokenize_directory(&directory, output, ignore, skip) {
+            if let Err(e) = tokenize_directory(
+                &directory,
+                output,
+                ignore,
+                skip,
+                max_depth,
+                no_vcs_ignore,
+                no_ignore,
+            ) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -73,8 +153,35 @@ fn main() {
             output,
             ignore,
             skip,
+            max_depth,
+            no_vcs_ignore,
+            no_ignore,
+            max_tokens,
+            sort,
         } => {
-            if let Err(e) = build_prompt_directory(&directory, output, ignore, skip) {
+            if let Err(e) = build_prompt_directory(
+                &directory,
+                output,
+                ignore,
+                skip,
+                max_depth,
+                no_vcs_ignore,
+                no_ignore,
+                max_tokens,
+                sort,
+            ) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::FilePrompt {
+            file,
+            output,
+            ignore,
+            skip,
+            tokens,
+        } => {
+            if let Err(e) = build_prompt_file(&file, output, ignore, skip, tokens) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
@@ -82,27 +189,77 @@ fn main() {
     }
 }
 
+/// Compiles `--ignore` glob strings into `PathPattern`s, warning on and
+/// discarding any that fail to parse.
+fn compile_ignore_globs(ignore_patterns: &[String]) -> Vec<PathPattern> {
+    ignore_patterns
+        .iter()
+        .filter_map(|p| {
+            PathPattern::new(p)
+                .map_err(|_| eprintln!("Warning: Invalid ignore pattern '{}'. Ignoring.", p))
+                .ok()
+        })
+        .collect()
+}
+
+/// Recursively walks `dir` (a descendant of, or equal to, `root`), collecting
+/// every file path that isn't excluded by `ignore_globs` or by `ignore`
+/// (auto-loaded `.gitignore`/`.ignore` rules). `ignore_globs` are matched
+/// against each entry's path relative to `root`, and each auto-loaded rule
+/// against the path relative to its own ignore file's directory, as the walk
+/// descends, so a pattern that
+/// matches a directory prunes that whole subtree instead of only hiding the
+/// directory entry itself. Recursion stops once `depth` reaches `max_depth`.
+/// Nested ignore files are picked up as their directory is entered.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    max_depth: Option<usize>,
+    ignore_globs: &[PathPattern],
+    ignore: &mut Ignore,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_dir = path.is_dir();
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+
+        if ignore_globs.iter().any(|p| p.matches(&relative)) || ignore.is_ignored(&path, is_dir) {
+            println!("Skipping ignored path: {}", path.display());
+            continue;
+        }
+
+        if is_dir {
+            if max_depth.map_or(true, |max| depth < max) {
+                ignore.push_dir(&path);
+                collect_files(root, &path, depth + 1, max_depth, ignore_globs, ignore, files)?;
+            }
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
 /// Tokenizes contents of files in the given directory and prints token counts
 fn tokenize_directory(
     dir_path: &str,
     output: Option<String>,
     ignore_patterns: Vec<String>,
     skip_substrings: Vec<String>,
+    max_depth: Option<usize>,
+    no_vcs_ignore: bool,
+    no_ignore: bool,
 ) -> Result<(), Box<dyn Error>> {
     let path = Path::new(dir_path);
     if !path.is_dir() {
         return Err(format!("{} is not a directory.", dir_path).into());
     }
 
-    // Compile ignore patterns into glob::Pattern
-    let ignore_globs: Vec<Pattern> = ignore_patterns
-        .iter()
-        .map(|p| Pattern::new(p).unwrap_or_else(|_| {
-            eprintln!("Warning: Invalid ignore pattern '{}'. Ignoring.", p);
-            // Return a pattern that matches nothing
-            Pattern::new("a^").unwrap()
-        }))
-        .collect();
+    let ignore_globs = compile_ignore_globs(&ignore_patterns);
 
     // Prepare the output: either a file or stdout
     let mut writer: Box<dyn Write> = match output {
@@ -121,47 +278,40 @@ fn tokenize_directory(
     // Initialize the tokenizer with the appropriate encoding
     let encoding = p50k_base()?;
 
-    // Iterate over each entry in the directory
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let file_path = entry.path();
-
-        if file_path.is_file() {
-            let file_name_os = entry.file_name();
-            let file_name_str = file_name_os.to_string_lossy();
-
-            // Check if file matches any ignore pattern
-            if ignore_globs.iter().any(|p| p.matches(&file_name_str)) {
-                println!("Skipping ignored file: {}", file_name_str);
-                continue;
-            }
-
-            // Read file contents
-            let contents = fs::read_to_string(&file_path).unwrap_or_else(|_| {
-                eprintln!("Warning: Could not read file {}", file_path.display());
-                String::from("[Could not read contents]")
-            });
-
-            // Process contents: skip lines containing any of the specified substrings
-            let processed_contents = if skip_substrings.is_empty() {
-                contents
-            } else {
-                contents
-                    .lines()
-                    .filter(|line| {
-                        !skip_substrings.iter().any(|substr| line.contains(substr))
-                    })
-                    .collect::<Vec<&str>>()
-                    .join("\n")
-            };
-
-            // Tokenize the processed contents
-            let tokens = encoding.encode_with_special_tokens(&processed_contents);
-            let token_count = tokens.len();
-
-            // Write to output
-            writeln!(writer, "{}   {} tokens", file_name_str, token_count)?;
-        }
+    let load_gitignore = !no_ignore && !no_vcs_ignore;
+    let load_dot_ignore = !no_ignore;
+    let mut ignore = Ignore::discover(path, load_gitignore, load_dot_ignore);
+    let mut files = Vec::new();
+    collect_files(path, path, 0, max_depth, &ignore_globs, &mut ignore, &mut files)?;
+
+    for file_path in files {
+        let relative_path = file_path.strip_prefix(path).unwrap_or(&file_path);
+
+        // Read file contents
+        let contents = fs::read_to_string(&file_path).unwrap_or_else(|_| {
+            eprintln!("Warning: Could not read file {}", file_path.display());
+            String::from("[Could not read contents]")
+        });
+
+        // Process contents: skip lines containing any of the specified substrings
+        let processed_contents = if skip_substrings.is_empty() {
+            contents
+        } else {
+            contents
+                .lines()
+                .filter(|line| {
+                    !skip_substrings.iter().any(|substr| line.contains(substr))
+                })
+                .collect::<Vec<&str>>()
+                .join("\n")
+        };
+
+        // Tokenize the processed contents
+        let tokens = encoding.encode_with_special_tokens(&processed_contents);
+        let token_count = tokens.len();
+
+        // Write to output
+        writeln!(writer, "{}   {} tokens", relative_path.display(), token_count)?;
     }
 
     Ok(())
@@ -173,21 +323,18 @@ fn build_prompt_directory(
     output: Option<String>,
     ignore_patterns: Vec<String>,
     skip_substrings: Vec<String>,
+    max_depth: Option<usize>,
+    no_vcs_ignore: bool,
+    no_ignore: bool,
+    max_tokens: Option<usize>,
+    sort: SortKey,
 ) -> Result<(), Box<dyn Error>> {
     let path = Path::new(dir_path);
     if !path.is_dir() {
         return Err(format!("{} is not a directory.", dir_path).into());
     }
 
-    // Compile ignore patterns into glob::Pattern
-    let ignore_globs: Vec<Pattern> = ignore_patterns
-        .iter()
-        .map(|p| Pattern::new(p).unwrap_or_else(|_| {
-            eprintln!("Warning: Invalid ignore pattern '{}'. Ignoring.", p);
-            // Return a pattern that matches nothing
-            Pattern::new("a^").unwrap()
-        }))
-        .collect();
+    let ignore_globs = compile_ignore_globs(&ignore_patterns);
 
     // Prepare the output: either a file or stdout
     let mut writer: Box<dyn Write> = match output {
@@ -203,28 +350,23 @@ fn build_prompt_directory(
         None => Box::new(io::stdout()),
     };
 
-    // Iterate over each entry in the directory
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let file_path = entry.path();
-
-        if file_path.is_file() {
-            let file_name_os = entry.file_name();
-            let file_name_str = file_name_os.to_string_lossy();
+    let load_gitignore = !no_ignore && !no_vcs_ignore;
+    let load_dot_ignore = !no_ignore;
+    let mut ignore = Ignore::discover(path, load_gitignore, load_dot_ignore);
+    let mut files = Vec::new();
+    collect_files(path, path, 0, max_depth, &ignore_globs, &mut ignore, &mut files)?;
 
-            // Check if file matches any ignore pattern
-            if ignore_globs.iter().any(|p| p.matches(&file_name_str)) {
-                println!("Skipping ignored file: {}", file_name_str);
-                continue;
-            }
-
-            // Read file contents
+    // Read and process every file up front so sorting/packing has the token
+    // count and size of each candidate to work with.
+    let encoding = p50k_base()?;
+    let mut entries: Vec<(PathBuf, String, usize, u64)> = files
+        .into_iter()
+        .map(|file_path| {
             let contents = fs::read_to_string(&file_path).unwrap_or_else(|_| {
                 eprintln!("Warning: Could not read file {}", file_path.display());
                 String::from("[Could not read contents]")
             });
 
-            // Process contents: skip lines containing any of the specified substrings
             let processed_contents = if skip_substrings.is_empty() {
                 contents
             } else {
@@ -237,9 +379,129 @@ fn build_prompt_directory(
                     .join("\n")
             };
 
-            // Write to output
-            writeln!(writer, "{}\n\n{}\n", file_name_str, processed_contents)?;
+            let token_count = encoding
+                .encode_with_special_tokens(&processed_contents)
+                .len();
+            let size = fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+
+            (file_path, processed_contents, token_count, size)
+        })
+        .collect();
+
+    match sort {
+        SortKey::Size => entries.sort_by_key(|(_, _, _, size)| *size),
+        SortKey::Tokens => entries.sort_by_key(|(_, _, tokens, _)| *tokens),
+        SortKey::Name => entries.sort_by(|a, b| a.0.cmp(&b.0)),
+    }
+
+    let mut total_tokens = 0usize;
+    let mut omitted = Vec::new();
+    let mut entries = entries.into_iter();
+
+    for (file_path, processed_contents, token_count, _) in &mut entries {
+        let relative_path = file_path.strip_prefix(path).unwrap_or(&file_path);
+
+        if let Some(budget) = max_tokens {
+            if total_tokens + token_count > budget {
+                // Budget exhausted: stop packing, everything left is omitted.
+                omitted.push(relative_path.to_path_buf());
+                break;
+            }
         }
+        total_tokens += token_count;
+
+        // Write to output: a per-file token count header, then the contents
+        writeln!(
+            writer,
+            "{} ({} tokens)\n\n{}\n",
+            relative_path.display(),
+            token_count,
+            processed_contents
+        )?;
+    }
+
+    omitted.extend(
+        entries.map(|(file_path, _, _, _)| file_path.strip_prefix(path).unwrap_or(&file_path).to_path_buf()),
+    );
+
+    writeln!(writer, "Total tokens: {}", total_tokens)?;
+
+    for path in &omitted {
+        println!("Omitted {} (would exceed token budget)", path.display());
+    }
+
+    Ok(())
+}
+
+/// Builds a prompt from a seed file and its transitive dependency graph,
+/// emitting each file in dependency order (leaves first).
+fn build_prompt_file(
+    file_path: &str,
+    output: Option<String>,
+    ignore_patterns: Vec<String>,
+    skip_substrings: Vec<String>,
+    show_tokens: bool,
+) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(file_path);
+    if !path.is_file() {
+        return Err(format!("{} is not a file.", file_path).into());
+    }
+
+    let ignore_globs = compile_ignore_globs(&ignore_patterns);
+    let files = deps::resolve_file_graph(path, &ignore_globs)?;
+    let root = path.parent().unwrap_or_else(|| Path::new("."));
+
+    // Prepare the output: either a file or stdout
+    let mut writer: Box<dyn Write> = match output {
+        Some(ref file_path) => {
+            let file = File::create(file_path).map_err(|e| {
+                format!(
+                    "Failed to create output file '{}': {}",
+                    file_path, e
+                )
+            })?;
+            Box::new(file)
+        }
+        None => Box::new(io::stdout()),
+    };
+
+    let encoding = if show_tokens { Some(p50k_base()?) } else { None };
+    let mut total_tokens = 0usize;
+
+    for file_path in files {
+        let relative_path = file_path.strip_prefix(root).unwrap_or(&file_path);
+
+        // Read file contents
+        let contents = fs::read_to_string(&file_path).unwrap_or_else(|_| {
+            eprintln!("Warning: Could not read file {}", file_path.display());
+            String::from("[Could not read contents]")
+        });
+
+        // Process contents: skip lines containing any of the specified substrings
+        let processed_contents = if skip_substrings.is_empty() {
+            contents
+        } else {
+            contents
+                .lines()
+                .filter(|line| {
+                    !skip_substrings.iter().any(|substr| line.contains(substr))
+                })
+                .collect::<Vec<&str>>()
+                .join("\n")
+        };
+
+        if let Some(encoding) = &encoding {
+            total_tokens += encoding
+                .encode_with_special_tokens(&processed_contents)
+                .len();
+        }
+
+        // Write to output
+        writeln!(writer, "{}\n\n{}\n", relative_path.display(), processed_contents)?;
+    }
+
+    if show_tokens {
+        writeln!(writer, "Total tokens: {}", total_tokens)?;
     }
 
     Ok(())
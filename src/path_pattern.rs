@@ -0,0 +1,99 @@
+//! Glob-to-regex compilation for path-aware ignore matching.
+//!
+//! Unlike [`glob::Pattern`], which only ever tested a bare file name, a
+//! [`PathPattern`] is matched against a whole path relative to the directory
+//! being walked, so patterns like `src/*.rs` or `**/target` behave the way
+//! users expect from `.gitignore`/ripgrep-style tools.
+
+use regex::Regex;
+
+/// Characters that are regex-special and must be escaped when they appear
+/// literally in a glob pattern.
+const SPECIAL_CHARS: &str = "()[]{}?*+-|^$\\.&~#";
+
+/// A compiled, path-aware glob matcher.
+pub struct PathPattern {
+    regex: Regex,
+}
+
+impl PathPattern {
+    /// Compiles `pattern` into a `PathPattern`. A leading `/` anchors the
+    /// pattern to the root of the walk; otherwise it may match starting at
+    /// any depth below the root.
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        let anchored = pattern.starts_with('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        Self::with_anchor(pattern, anchored)
+    }
+
+    /// Compiles `pattern` with explicit anchoring, for callers (such as the
+    /// ignore-file subsystem) that decide anchoring themselves.
+    pub fn with_anchor(pattern: &str, anchored: bool) -> Result<Self, regex::Error> {
+        let regex = Regex::new(&translate(pattern, anchored))?;
+        Ok(PathPattern { regex })
+    }
+
+    /// Tests `relative_path` (a `/`-separated path relative to the walked
+    /// root) against this pattern.
+    pub fn matches(&self, relative_path: &str) -> bool {
+        self.regex.is_match(relative_path)
+    }
+}
+
+/// Translates a glob pattern into an anchored regex string evaluated against
+/// a root-relative path: `*/` becomes `(?:.*/)?`, a standalone `*` becomes
+/// `[^/]*`, `**` becomes `.*` (crossing directory separators), `?` becomes
+/// `[^/]`, and `[...]` character classes pass through unchanged. Every other
+/// character is regex-escaped.
+fn translate(glob: &str, anchored: bool) -> String {
+    let mut out = String::with_capacity(glob.len() + 8);
+    out.push('^');
+    if !anchored {
+        out.push_str("(?:.*/)?");
+    }
+
+    let chars: Vec<char> = glob.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'/') => {
+                out.push_str("(?:.*/)?");
+                i += 2;
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                out.push_str(".*");
+                i += 2;
+            }
+            '*' => {
+                out.push_str("[^/]*");
+                i += 1;
+            }
+            '?' => {
+                out.push_str("[^/]");
+                i += 1;
+            }
+            '[' => {
+                out.push('[');
+                i += 1;
+                while i < chars.len() && chars[i] != ']' {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+                if i < chars.len() {
+                    out.push(']');
+                    i += 1;
+                }
+            }
+            c => {
+                if SPECIAL_CHARS.contains(c) || c.is_whitespace() {
+                    out.push('\\');
+                }
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out.push('$');
+    out
+}